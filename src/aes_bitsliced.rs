@@ -0,0 +1,306 @@
+use crate::aes::round_keys_with;
+use crate::cipher::BlockCipher;
+
+// Bitsliced AES. Instead of storing bytes, the state is stored as eight
+// bit planes, where plane j holds bit j of every byte. Each plane is a
+// u128 with 16 bits per block, so eight blocks are processed at once,
+// and the byte at index i of block n lives at bit 16 * n + i. Every
+// step is then plain bitwise logic and shifts by constant amounts, with
+// no table lookups or branches depending on secret data.
+
+type Planes = [u128; 8];
+
+const LANES: usize = 8;
+
+// Bit 0 of every block.
+const LANE_MASK: u128 = 0x0001_0001_0001_0001_0001_0001_0001_0001;
+
+fn pack(blocks: &[u8]) -> Planes {
+    let mut planes = [0; 8];
+    for (i, &byte) in blocks.iter().enumerate() {
+        for (j, plane) in planes.iter_mut().enumerate() {
+            *plane |= ((byte >> j) as u128 & 1) << i;
+        }
+    }
+    planes
+}
+
+fn unpack(planes: &Planes, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| {
+            planes
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (j, plane)| byte | ((plane >> i) as u8 & 1) << j)
+        })
+        .collect()
+}
+
+fn xor(a: &Planes, b: &Planes) -> Planes {
+    let mut result = *a;
+    result.iter_mut().zip(b).for_each(|(x, y)| *x ^= y);
+    result
+}
+
+fn gf_mul(a: &Planes, b: &Planes) -> Planes {
+    // Schoolbook multiplication into 15 bits, then reduce using
+    // x^8 = x^4 + x^3 + x + 1.
+    let mut product = [0u128; 15];
+    for i in 0..8 {
+        for j in 0..8 {
+            product[i+j] ^= a[i] & b[j];
+        }
+    }
+    for k in (8..15).rev() {
+        product[k-4] ^= product[k];
+        product[k-5] ^= product[k];
+        product[k-7] ^= product[k];
+        product[k-8] ^= product[k];
+    }
+    let mut result = [0; 8];
+    result.copy_from_slice(&product[..8]);
+    result
+}
+
+fn gf_inverse(a: &Planes) -> Planes {
+    // a^254 = a^-1, and conveniently 0^254 = 0 just like the S-box
+    // wants it.
+    let a2 = gf_mul(a, a);
+    let a3 = gf_mul(&a2, a);
+    let a6 = gf_mul(&a3, &a3);
+    let a12 = gf_mul(&a6, &a6);
+    let a15 = gf_mul(&a12, &a3);
+    let a30 = gf_mul(&a15, &a15);
+    let a60 = gf_mul(&a30, &a30);
+    let a120 = gf_mul(&a60, &a60);
+    let a240 = gf_mul(&a120, &a120);
+    let a252 = gf_mul(&a240, &a12);
+    gf_mul(&a252, &a2)
+}
+
+fn sub_bytes(state: &Planes) -> Planes {
+    // Invert, then apply the affine transformation
+    // b'[i] = b[i] ^ b[i+4] ^ b[i+5] ^ b[i+6] ^ b[i+7] ^ 0x63[i]
+    let b = gf_inverse(state);
+    let mut result = [0; 8];
+    for i in 0..8 {
+        result[i] = b[i] ^ b[(i+4)%8] ^ b[(i+5)%8] ^ b[(i+6)%8] ^ b[(i+7)%8];
+        if 0x63 >> i & 1 == 1 {
+            result[i] = !result[i];
+        }
+    }
+    result
+}
+
+fn inv_sub_bytes(state: &Planes) -> Planes {
+    // Undo the affine transformation
+    // b[i] = b'[i+2] ^ b'[i+5] ^ b'[i+7] ^ 0x05[i]
+    // then invert.
+    let b = state;
+    let mut result = [0; 8];
+    for i in 0..8 {
+        result[i] = b[(i+2)%8] ^ b[(i+5)%8] ^ b[(i+7)%8];
+        if 0x05 >> i & 1 == 1 {
+            result[i] = !result[i];
+        }
+    }
+    gf_inverse(&result)
+}
+
+fn permute(state: &Planes, source: impl Fn(usize) -> usize) -> Planes {
+    // Moves byte source(i) to byte i in every block.
+    let mut result = [0; 8];
+    for (plane, out) in state.iter().zip(result.iter_mut()) {
+        for i in 0..16 {
+            *out |= (plane >> source(i) & LANE_MASK) << i;
+        }
+    }
+    result
+}
+
+fn shift_rows(state: &Planes) -> Planes {
+    // Byte index is column * 4 + row, and row r takes from column c + r.
+    permute(state, |i| {
+        let (column, row) = (i / 4, i % 4);
+        (column + row) % 4 * 4 + row
+    })
+}
+
+fn inv_shift_rows(state: &Planes) -> Planes {
+    permute(state, |i| {
+        let (column, row) = (i / 4, i % 4);
+        (column + 4 - row) % 4 * 4 + row
+    })
+}
+
+fn rotate_rows(state: &Planes, n: u32) -> Planes {
+    // Row r of each column takes from row r + n. Columns are four bit
+    // groups, so this is a rotation within every nibble.
+    let low = 0x1111_1111_1111_1111_1111_1111_1111_1111u128 * ((1 << (4 - n)) - 1);
+    let mut result = *state;
+    for plane in result.iter_mut() {
+        *plane = (*plane >> n & low) | (*plane << (4 - n) & !low);
+    }
+    result
+}
+
+fn xtime(a: &Planes) -> Planes {
+    // Multiplication by x, reducing by 0x1b if the top bit was set.
+    [a[7], a[0] ^ a[7], a[1], a[2] ^ a[7], a[3] ^ a[7], a[4], a[5], a[6]]
+}
+
+fn mix_columns(state: &Planes) -> Planes {
+    // 2a[r] ^ 3a[r+1] ^ a[r+2] ^ a[r+3]
+    //   = 2(a[r] ^ a[r+1]) ^ a[r+1] ^ a[r+2] ^ a[r+3]
+    let a1 = rotate_rows(state, 1);
+    let a2 = rotate_rows(state, 2);
+    let a3 = rotate_rows(state, 3);
+    xor(&xtime(&xor(state, &a1)), &xor(&a1, &xor(&a2, &a3)))
+}
+
+fn inv_mix_columns(state: &Planes) -> Planes {
+    // InvMixColumns is MixColumns after adding 4(a[r] ^ a[r+2]) to
+    // each byte.
+    let a2 = rotate_rows(state, 2);
+    let u = xtime(&xtime(&xor(state, &a2)));
+    mix_columns(&xor(state, &u))
+}
+
+fn broadcast(key: &[u8]) -> Planes {
+    // Copies a round key into every block.
+    let mut planes = pack(key);
+    for plane in planes.iter_mut() {
+        *plane *= LANE_MASK;
+    }
+    planes
+}
+
+fn sub_word(word: u32) -> u32 {
+    let planes = sub_bytes(&pack(&word.to_be_bytes()));
+    let bytes = unpack(&planes, 4);
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Table-free, constant-time AES that encrypts up to eight blocks at
+/// once. Slower than the lookup table implementations for single
+/// blocks.
+pub struct AesBitsliced {
+    keys: Vec<Planes>,
+}
+
+#[allow(dead_code)]
+impl AesBitsliced {
+    /// Takes a 16, 24 or 32 byte key for AES-128, AES-192 or AES-256.
+    pub fn new(key: &[u8]) -> Self {
+        // The key schedule needs the S-box too, so it gets the
+        // bitsliced one.
+        let keys = round_keys_with(key, sub_word)
+            .iter()
+            .map(|key| {
+                let bytes: Vec<u8> = key.iter().flat_map(|word| word.to_be_bytes()).collect();
+                broadcast(&bytes)
+            })
+            .collect();
+        AesBitsliced { keys }
+    }
+
+    /// Encrypts any number of whole blocks, eight at a time.
+    pub fn encrypt_blocks(&self, input: &[u8]) -> Vec<u8> {
+        assert!(input.len().is_multiple_of(16), "Input is not block aligned");
+        let keys = &self.keys;
+        let rounds = keys.len() - 1;
+        input
+            .chunks(16 * LANES)
+            .flat_map(|blocks| {
+                let mut state = xor(&pack(blocks), &keys[0]);
+                for key in &keys[1..rounds] {
+                    state = xor(&mix_columns(&shift_rows(&sub_bytes(&state))), key);
+                }
+                state = xor(&shift_rows(&sub_bytes(&state)), &keys[rounds]);
+                unpack(&state, blocks.len())
+            })
+            .collect()
+    }
+
+    /// Decrypts any number of whole blocks, eight at a time.
+    pub fn decrypt_blocks(&self, input: &[u8]) -> Vec<u8> {
+        assert!(input.len().is_multiple_of(16), "Input is not block aligned");
+        let keys = &self.keys;
+        let rounds = keys.len() - 1;
+        input
+            .chunks(16 * LANES)
+            .flat_map(|blocks| {
+                let mut state = xor(&pack(blocks), &keys[rounds]);
+                for key in keys[1..rounds].iter().rev() {
+                    state = inv_sub_bytes(&inv_shift_rows(&state));
+                    state = inv_mix_columns(&xor(&state, key));
+                }
+                state = xor(&inv_sub_bytes(&inv_shift_rows(&state)), &keys[0]);
+                unpack(&state, blocks.len())
+            })
+            .collect()
+    }
+}
+
+impl BlockCipher for AesBitsliced {
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(16, block.len(), "Invalid block length");
+        self.encrypt_blocks(block)
+    }
+
+    fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(16, block.len(), "Invalid block length");
+        self.decrypt_blocks(block)
+    }
+}
+
+#[test]
+fn sub_bytes_test() {
+    use crate::aes::{INV_S_BOX, S_BOX};
+    let all: Vec<u8> = (0..=255).collect();
+    for chunk in all.chunks(128) {
+        let planes = pack(chunk);
+        let expected: Vec<u8> = chunk.iter().map(|&b| S_BOX[b as usize]).collect();
+        assert_eq!(expected, unpack(&sub_bytes(&planes), 128));
+        let expected: Vec<u8> = chunk.iter().map(|&b| INV_S_BOX[b as usize]).collect();
+        assert_eq!(expected, unpack(&inv_sub_bytes(&planes), 128));
+    }
+}
+
+#[test]
+fn bitsliced_test() {
+    use crate::hex::from_hex;
+    let aes = AesBitsliced::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let input = from_hex("3243f6a8885a308d313198a2e0370734");
+    let expected = from_hex("3925841d02dc09fbdc118597196a0b32");
+    assert_eq!(expected, aes.encrypt_block(&input));
+    assert_eq!(input, aes.decrypt_block(&expected));
+}
+
+#[test]
+fn bitsliced_matches_reference_test() {
+    use crate::aes::Aes;
+    use crate::util::Rng;
+    let mut rng = Rng::new(11);
+    for &key_size in [16, 24, 32].iter() {
+        let key = rng.bytes(key_size);
+        // Not a multiple of eight blocks, so the last batch is partial.
+        let input = rng.bytes(16 * 13);
+        let reference = Aes::new(&key);
+        let bitsliced = AesBitsliced::new(&key);
+        let expected: Vec<u8> = input
+            .chunks(16)
+            .flat_map(|block| reference.encrypt_block(block))
+            .collect();
+        assert_eq!(expected, bitsliced.encrypt_blocks(&input));
+        let expected: Vec<u8> = input
+            .chunks(16)
+            .flat_map(|block| reference.decrypt_block(block))
+            .collect();
+        assert_eq!(expected, bitsliced.decrypt_blocks(&input));
+    }
+}
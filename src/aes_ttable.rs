@@ -0,0 +1,151 @@
+use crate::aes::{equivalent_inverse_keys, round_keys, Key, INV_S_BOX, MUL_11, MUL_13, MUL_14, MUL_2, MUL_3, MUL_9, S_BOX};
+use crate::cipher::BlockCipher;
+
+// Each T-table entry is a whole column of a round: the byte run through
+// the S-box and multiplied by the matching MixColumns coefficients, so
+// SubBytes, ShiftRows and MixColumns collapse into four lookups and
+// three xors per column. The other three tables are byte rotations of
+// the first one, one per row.
+
+const fn t_table(rotation: u32, inverse: bool) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let word = if inverse {
+            let s = INV_S_BOX[i] as usize;
+            u32::from_be_bytes([MUL_14[s], MUL_9[s], MUL_13[s], MUL_11[s]])
+        } else {
+            let s = S_BOX[i] as usize;
+            u32::from_be_bytes([MUL_2[s], s as u8, s as u8, MUL_3[s]])
+        };
+        table[i] = word.rotate_right(8 * rotation);
+        i += 1;
+    }
+    table
+}
+
+const TE: [[u32; 256]; 4] = [
+    t_table(0, false), t_table(1, false), t_table(2, false), t_table(3, false),
+];
+
+const TD: [[u32; 256]; 4] = [
+    t_table(0, true), t_table(1, true), t_table(2, true), t_table(3, true),
+];
+
+fn lookup(tables: &[[u32; 256]; 4], a: u32, b: u32, c: u32, d: u32) -> u32 {
+    tables[0][(a >> 24) as usize]
+        ^ tables[1][(b >> 16 & 0xff) as usize]
+        ^ tables[2][(c >> 8 & 0xff) as usize]
+        ^ tables[3][(d & 0xff) as usize]
+}
+
+fn substitute(s_box: &[u8; 256], a: u32, b: u32, c: u32, d: u32) -> u32 {
+    u32::from_be_bytes([
+        s_box[(a >> 24) as usize],
+        s_box[(b >> 16 & 0xff) as usize],
+        s_box[(c >> 8 & 0xff) as usize],
+        s_box[(d & 0xff) as usize],
+    ])
+}
+
+/// AES using T-tables. Much faster than `aes::Aes`, but just as leaky
+/// when it comes to cache timing.
+pub struct AesTTable {
+    keys: Vec<Key>,
+    inv_keys: Vec<Key>,
+}
+
+impl AesTTable {
+    /// Takes a 16, 24 or 32 byte key for AES-128, AES-192 or AES-256.
+    pub fn new(key: &[u8]) -> Self {
+        let keys = round_keys(key);
+        // Decryption uses the equivalent inverse cipher so it can use
+        // T-tables as well.
+        let inv_keys = equivalent_inverse_keys(&keys);
+        AesTTable { keys, inv_keys }
+    }
+}
+
+fn load(block: &[u8], key: &Key) -> [u32; 4] {
+    assert_eq!(16, block.len(), "Invalid block length");
+    let mut state = [0; 4];
+    for (i, word) in block.chunks(4).enumerate() {
+        state[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]) ^ key[i];
+    }
+    state
+}
+
+fn store(state: [u32; 4]) -> Vec<u8> {
+    state.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
+
+impl BlockCipher for AesTTable {
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        let keys = &self.keys;
+        let rounds = keys.len() - 1;
+        let mut s = load(block, &keys[0]);
+        // ShiftRows means column i takes row r from column i + r.
+        for key in &keys[1..rounds] {
+            let mut t = [0; 4];
+            for i in 0..4 {
+                t[i] = lookup(&TE, s[i], s[(i+1)%4], s[(i+2)%4], s[(i+3)%4]) ^ key[i];
+            }
+            s = t;
+        }
+        let mut t = [0; 4];
+        for i in 0..4 {
+            t[i] = substitute(&S_BOX, s[i], s[(i+1)%4], s[(i+2)%4], s[(i+3)%4]) ^ keys[rounds][i];
+        }
+        store(t)
+    }
+
+    fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        let keys = &self.inv_keys;
+        let rounds = keys.len() - 1;
+        let mut s = load(block, &keys[0]);
+        // InvShiftRows means column i takes row r from column i - r.
+        for key in &keys[1..rounds] {
+            let mut t = [0; 4];
+            for i in 0..4 {
+                t[i] = lookup(&TD, s[i], s[(i+3)%4], s[(i+2)%4], s[(i+1)%4]) ^ key[i];
+            }
+            s = t;
+        }
+        let mut t = [0; 4];
+        for i in 0..4 {
+            t[i] = substitute(&INV_S_BOX, s[i], s[(i+3)%4], s[(i+2)%4], s[(i+1)%4]) ^ keys[rounds][i];
+        }
+        store(t)
+    }
+}
+
+#[test]
+fn t_table_test() {
+    use crate::hex::from_hex;
+    let aes = AesTTable::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let input = from_hex("3243f6a8885a308d313198a2e0370734");
+    let expected = from_hex("3925841d02dc09fbdc118597196a0b32");
+    assert_eq!(expected, aes.encrypt_block(&input));
+    assert_eq!(input, aes.decrypt_block(&expected));
+}
+
+#[test]
+fn matches_reference_test() {
+    use crate::aes::Aes;
+    use crate::util::Rng;
+    let mut rng = Rng::new(10);
+    for &key_size in [16, 24, 32].iter() {
+        for _ in 0..50 {
+            let key = rng.bytes(key_size);
+            let block = rng.bytes(16);
+            let reference = Aes::new(&key);
+            let fast = AesTTable::new(&key);
+            assert_eq!(reference.encrypt_block(&block), fast.encrypt_block(&block));
+            assert_eq!(reference.decrypt_block(&block), fast.decrypt_block(&block));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::aes::Aes;
+use crate::ctr::{Counter, Ctr};
+use crate::modes::{cbc_decrypt, cbc_encrypt};
+use crate::util::{fixed_xor, Rng};
+
+/// In CBC, flipping a bit in one ciphertext block flips the same bit in
+/// the next plaintext block (and scrambles the block itself). This is
+/// the xor to apply to the preceding ciphertext block to turn `known`
+/// plaintext into `desired` plaintext.
+pub fn cbc_flip_delta(known: &[u8], desired: &[u8]) -> Vec<u8> {
+    assert_eq!(known.len(), desired.len(), "Blocks differ in length");
+    fixed_xor(known, desired)
+}
+
+/// Applies the flip to `ciphertext`, so that block `block` (counting
+/// from zero, so at least one, as block zero is preceded by the IV)
+/// decrypts to `desired` instead of `known`.
+pub fn cbc_flip(ciphertext: &[u8], block_size: usize, block: usize, known: &[u8], desired: &[u8]) -> Vec<u8> {
+    assert!(0 < block, "Flip the IV to change the first block");
+    let delta = cbc_flip_delta(known, desired);
+    let start = (block - 1) * block_size;
+    let mut result = ciphertext.to_vec();
+    for (i, d) in delta.iter().enumerate() {
+        result[start + i] ^= d;
+    }
+    result
+}
+
+#[test]
+fn cbc_flip_test() {
+    let aes = Aes::new(b"YELLOW SUBMARINE");
+    let iv = [0; 16];
+    let ciphertext = cbc_encrypt(&aes, &iv, b"first block 0001second block 002third block 0003");
+    let flipped = cbc_flip(&ciphertext, 16, 2, b"third block 0003", b"changed block 99");
+    let plaintext = cbc_decrypt(&aes, &iv, &flipped).unwrap();
+    assert_eq!(b"first block 0001".to_vec(), plaintext[..16].to_vec());
+    assert_eq!(b"changed block 99".to_vec(), plaintext[32..].to_vec());
+}
+
+pub const USER_DATA_PREFIX: &str = "comment1=cooking%20MCs;userdata=";
+pub const USER_DATA_SUFFIX: &str = ";comment2=%20like%20a%20pound%20of%20bacon";
+
+/// Puts user data between the prefix and suffix, quoting `;` and `=`
+/// so the user can't add fields of their own.
+pub fn wrap_user_data(input: &str) -> String {
+    let quoted = input.replace(';', "%3B").replace('=', "%3D");
+    format!("{}{}{}", USER_DATA_PREFIX, quoted, USER_DATA_SUFFIX)
+}
+
+pub fn is_admin(plaintext: &[u8]) -> bool {
+    String::from_utf8_lossy(plaintext)
+        .split(';')
+        .any(|field| field == "admin=true")
+}
+
+#[test]
+fn wrap_user_data_test() {
+    let wrapped = wrap_user_data(";admin=true");
+    assert_eq!("comment1=cooking%20MCs;userdata=%3Badmin%3Dtrue;comment2=%20like%20a%20pound%20of%20bacon",
+               wrapped);
+    assert!(!is_admin(wrapped.as_bytes()));
+    assert!(is_admin(b"foo=bar;admin=true;baz=qux"));
+}
+
+/// Encrypts wrapped user data under AES-CBC with a random key and IV.
+#[allow(dead_code)]
+pub struct CbcUserDataOracle {
+    aes: Aes,
+    iv: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl CbcUserDataOracle {
+    pub fn new(rng: &mut Rng) -> Self {
+        CbcUserDataOracle { aes: Aes::new(&rng.bytes(16)), iv: rng.bytes(16) }
+    }
+
+    pub fn encrypt(&self, input: &str) -> Vec<u8> {
+        cbc_encrypt(&self.aes, &self.iv, wrap_user_data(input).as_bytes())
+    }
+
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        cbc_decrypt(&self.aes, &self.iv, ciphertext)
+            .map(|plaintext| is_admin(&plaintext))
+            .unwrap_or(false)
+    }
+}
+
+/// Gets `;admin=true;` past the quoting by sending a block with
+/// harmless stand-ins for `;` and `=`, after a block of filler that we
+/// then flip to turn them back into the real thing.
+#[allow(dead_code)]
+pub fn cbc_inject_admin<F>(encrypt: F, prefix_len: usize) -> Vec<u8>
+where F: Fn(&str) -> Vec<u8> {
+    let block_size = 16;
+    let alignment = (block_size - prefix_len % block_size) % block_size;
+    let known = "AAAA:admin<true:";
+    let desired = "AAAA;admin=true;";
+    let filler = "A".repeat(alignment + block_size);
+    let ciphertext = encrypt(&(filler + known));
+    let block = (prefix_len + alignment) / block_size + 1;
+    cbc_flip(&ciphertext, block_size, block, known.as_bytes(), desired.as_bytes())
+}
+
+#[test]
+fn cbc_inject_admin_test() {
+    let oracle = CbcUserDataOracle::new(&mut Rng::new(22));
+    assert!(!oracle.is_admin(&oracle.encrypt(";admin=true;")));
+    let forged = cbc_inject_admin(|input| oracle.encrypt(input), USER_DATA_PREFIX.len());
+    assert!(oracle.is_admin(&forged));
+}
+
+/// Encrypts wrapped user data under AES-CTR with a random key and
+/// nonce.
+#[allow(dead_code)]
+pub struct CtrUserDataOracle {
+    ctr: Ctr<Aes>,
+}
+
+#[allow(dead_code)]
+impl CtrUserDataOracle {
+    pub fn new(rng: &mut Rng) -> Self {
+        let nonce = rng.next_u64();
+        CtrUserDataOracle { ctr: Ctr::new(Aes::new(&rng.bytes(16)), Counter::LittleEndian { nonce }) }
+    }
+
+    pub fn encrypt(&self, input: &str) -> Vec<u8> {
+        self.ctr.apply(0, wrap_user_data(input).as_bytes())
+    }
+
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        is_admin(&self.ctr.apply(0, ciphertext))
+    }
+}
+
+/// Like `cbc_inject_admin`, but in CTR flipping a ciphertext bit flips
+/// the same plaintext bit, so there is no need for a sacrificial block
+/// or any alignment.
+#[allow(dead_code)]
+pub fn ctr_inject_admin<F>(encrypt: F, prefix_len: usize) -> Vec<u8>
+where F: Fn(&str) -> Vec<u8> {
+    let known = ":admin<true:";
+    let desired = ";admin=true;";
+    let mut ciphertext = encrypt(known);
+    let delta = fixed_xor(known.as_bytes(), desired.as_bytes());
+    for (i, d) in delta.iter().enumerate() {
+        ciphertext[prefix_len + i] ^= d;
+    }
+    ciphertext
+}
+
+#[test]
+fn ctr_inject_admin_test() {
+    let oracle = CtrUserDataOracle::new(&mut Rng::new(23));
+    assert!(!oracle.is_admin(&oracle.encrypt(";admin=true;")));
+    let forged = ctr_inject_admin(|input| oracle.encrypt(input), USER_DATA_PREFIX.len());
+    assert!(oracle.is_admin(&forged));
+}
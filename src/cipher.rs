@@ -0,0 +1,25 @@
+/// A keyed permutation over fixed-size blocks. Modes of operation and
+/// attacks are written against this, so they work the same on AES as
+/// on any toy or deliberately weakened cipher.
+#[allow(dead_code)]
+pub trait BlockCipher {
+    /// Block size in bytes.
+    fn block_size(&self) -> usize;
+
+    /// Encrypts exactly one block.
+    fn encrypt_block(&self, block: &[u8]) -> Vec<u8>;
+
+    /// Decrypts exactly one block.
+    fn decrypt_block(&self, block: &[u8]) -> Vec<u8>;
+}
+
+#[test]
+fn block_cipher_test() {
+    use crate::aes::Aes;
+    let cipher: Box<dyn BlockCipher> = Box::new(Aes::new(b"YELLOW SUBMARINE"));
+    assert_eq!(16, cipher.block_size());
+    let block = b"sixteen byte msg";
+    let encrypted = cipher.encrypt_block(block);
+    assert_ne!(block.to_vec(), encrypted);
+    assert_eq!(block.to_vec(), cipher.decrypt_block(&encrypted));
+}
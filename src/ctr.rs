@@ -0,0 +1,129 @@
+use crate::cipher::BlockCipher;
+use crate::util::fixed_xor;
+
+/// Layout of the counter block that gets encrypted to produce each
+/// block of keystream.
+#[derive(Clone, Copy)]
+pub enum Counter {
+    /// 64-bit little-endian nonce followed by a 64-bit little-endian
+    /// block count starting at zero, as used by cryptopals.
+    LittleEndian { nonce: u64 },
+    /// A single 128-bit big-endian counter, incremented per block, as
+    /// used by NIST SP 800-38A.
+    #[allow(dead_code)]
+    BigEndian { initial: u128 },
+}
+
+impl Counter {
+    fn block(&self, index: u64) -> [u8; 16] {
+        match *self {
+            Counter::LittleEndian { nonce } => {
+                let mut block = [0; 16];
+                block[..8].copy_from_slice(&nonce.to_le_bytes());
+                block[8..].copy_from_slice(&index.to_le_bytes());
+                block
+            },
+            Counter::BigEndian { initial } => {
+                initial.wrapping_add(index as u128).to_be_bytes()
+            },
+        }
+    }
+}
+
+/// CTR mode turns a block cipher into a seekable keystream, so
+/// encryption and decryption are the same operation and can start at
+/// any byte offset.
+pub struct Ctr<C: BlockCipher> {
+    cipher: C,
+    counter: Counter,
+}
+
+impl<C: BlockCipher> Ctr<C> {
+    pub fn new(cipher: C, counter: Counter) -> Self {
+        assert_eq!(16, cipher.block_size(), "CTR needs a 128-bit block cipher");
+        Ctr { cipher, counter }
+    }
+
+    /// Returns `len` bytes of keystream starting at byte `offset`.
+    pub fn keystream(&self, offset: usize, len: usize) -> Vec<u8> {
+        if len == 0 {
+            return vec![];
+        }
+        let first_block = offset / 16;
+        let last_block = (offset + len - 1) / 16;
+        let skip = offset % 16;
+        (first_block..=last_block)
+            .flat_map(|i| self.cipher.encrypt_block(&self.counter.block(i as u64)))
+            .skip(skip)
+            .take(len)
+            .collect()
+    }
+
+    /// Encrypts or decrypts `data`, which starts at byte `offset` of
+    /// the stream.
+    pub fn apply(&self, offset: usize, data: &[u8]) -> Vec<u8> {
+        fixed_xor(data, &self.keystream(offset, data.len()))
+    }
+
+    /// Replaces the plaintext at byte `offset` of `ciphertext` with
+    /// `new_text`, extending the ciphertext if needed. Only the edited
+    /// part needs any keystream.
+    pub fn edit(&self, ciphertext: &[u8], offset: usize, new_text: &[u8]) -> Vec<u8> {
+        assert!(offset <= ciphertext.len(), "Offset past the end of the ciphertext");
+        let mut result = ciphertext.to_vec();
+        result.resize(ciphertext.len().max(offset + new_text.len()), 0);
+        result[offset..offset + new_text.len()].copy_from_slice(&self.apply(offset, new_text));
+        result
+    }
+}
+
+#[test]
+fn ctr_nist_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // NIST SP 800-38A F.5.1 and F.5.2.
+    let aes = Aes::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let ctr = Ctr::new(aes, Counter::BigEndian {
+        initial: 0xf0f1f2f3f4f5f6f7f8f9fafbfcfdfeff,
+    });
+    let plaintext = from_hex(concat!(
+        "6bc1bee22e409f96e93d7e117393172a",
+        "ae2d8a571e03ac9c9eb76fac45af8e51",
+        "30c81c46a35ce411e5fbc1191a0a52ef",
+        "f69f2445df4f9b17ad2b417be66c3710",
+    ));
+    let ciphertext = from_hex(concat!(
+        "874d6191b620e3261bef6864990db6ce",
+        "9806f66b7970fdff8617187bb9fffdff",
+        "5ae4df3edbd5d35e5b4f09020db03eab",
+        "1e031dda2fbe03d1792170a0f3009cee",
+    ));
+    assert_eq!(ciphertext, ctr.apply(0, &plaintext));
+    assert_eq!(plaintext, ctr.apply(0, &ciphertext));
+    // Seeking into the middle of the stream.
+    assert_eq!(plaintext[20..37].to_vec(), ctr.apply(20, &ciphertext[20..37]));
+}
+
+#[test]
+fn ctr_cryptopals_test() {
+    use crate::aes::Aes;
+    use crate::base64::from_base64;
+    let ctr = Ctr::new(Aes::new(b"YELLOW SUBMARINE"), Counter::LittleEndian { nonce: 0 });
+    let ciphertext = from_base64(
+        "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ=="
+    );
+    assert_eq!(b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ".to_vec(),
+               ctr.apply(0, &ciphertext));
+}
+
+#[test]
+fn ctr_edit_test() {
+    use crate::aes::Aes;
+    let ctr = Ctr::new(Aes::new(b"YELLOW SUBMARINE"), Counter::LittleEndian { nonce: 7 });
+    let ciphertext = ctr.apply(0, b"We all live in a yellow submarine");
+    let edited = ctr.edit(&ciphertext, 17, b"purple");
+    assert_eq!(b"We all live in a purple submarine".to_vec(), ctr.apply(0, &edited));
+    let extended = ctr.edit(&ciphertext, 33, b"s, yellow submarines");
+    assert_eq!(b"We all live in a yellow submarines, yellow submarines".to_vec(),
+               ctr.apply(0, &extended));
+}
@@ -0,0 +1,152 @@
+use crate::aes::Aes;
+use crate::ctr::{Counter, Ctr};
+use crate::util::{fixed_xor, single_byte_xor_key, transpose, Rng};
+
+/// Encrypts under AES-CTR with a random key and nonce, and offers to
+/// edit ciphertexts in place without revealing the plaintext.
+#[allow(dead_code)]
+pub struct EditOracle {
+    ctr: Ctr<Aes>,
+}
+
+#[allow(dead_code)]
+impl EditOracle {
+    pub fn new(rng: &mut Rng) -> Self {
+        let nonce = rng.next_u64();
+        EditOracle { ctr: Ctr::new(Aes::new(&rng.bytes(16)), Counter::LittleEndian { nonce }) }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.ctr.apply(0, plaintext)
+    }
+
+    pub fn edit(&self, ciphertext: &[u8], offset: usize, new_text: &[u8]) -> Vec<u8> {
+        self.ctr.edit(ciphertext, offset, new_text)
+    }
+}
+
+/// Recovers the plaintext through an edit oracle. Editing in the
+/// ciphertext itself as the new plaintext xors it with the keystream
+/// once more, which gives back the plaintext.
+#[allow(dead_code)]
+pub fn recover_via_edit<F>(ciphertext: &[u8], edit: F) -> Vec<u8>
+where F: Fn(&[u8], usize, &[u8]) -> Vec<u8> {
+    edit(ciphertext, 0, ciphertext)
+}
+
+#[test]
+fn recover_via_edit_test() {
+    let oracle = EditOracle::new(&mut Rng::new(23));
+    let plaintext = b"I'm back and I'm ringin' the bell \nA rockin' on the mike while the fly girls yell";
+    let ciphertext = oracle.encrypt(plaintext);
+    let recovered = recover_via_edit(&ciphertext, |c, offset, new_text| oracle.edit(c, offset, new_text));
+    assert_eq!(plaintext.to_vec(), recovered);
+}
+
+// Encrypting many messages under the same key and nonce in CTR reuses
+// the keystream, which turns it into repeating-key xor where every
+// column of ciphertext bytes is xored with the same keystream byte.
+// Each column can then be solved as single-byte xor.
+
+/// Recovers the keystream up to the length of the shortest ciphertext.
+#[allow(dead_code)]
+pub fn break_fixed_nonce(ciphertexts: &[Vec<u8>]) -> Vec<u8> {
+    let len = ciphertexts.iter().map(Vec::len).min().expect("No ciphertexts");
+    let truncated: Vec<Vec<u8>> = ciphertexts.iter().map(|c| c[..len].to_vec()).collect();
+    transpose(truncated, 0)
+        .iter()
+        .map(|column| single_byte_xor_key(column))
+        .collect()
+}
+
+/// Extends the keystream past the shortest ciphertext, column by
+/// column, using only the ciphertexts that are long enough, for as
+/// long as there are at least `min_samples` of them. Gets less
+/// reliable the fewer samples there are.
+#[allow(dead_code)]
+pub fn extend_keystream(keystream: &[u8], ciphertexts: &[Vec<u8>], min_samples: usize) -> Vec<u8> {
+    let mut keystream = keystream.to_vec();
+    loop {
+        let column: Vec<u8> = ciphertexts
+            .iter()
+            .filter_map(|c| c.get(keystream.len()).cloned())
+            .collect();
+        if column.is_empty() || column.len() < min_samples {
+            return keystream;
+        }
+        keystream.push(single_byte_xor_key(&column));
+    }
+}
+
+/// Fixes up the keystream from a guess that `ciphertext` decrypts to
+/// `plaintext` at `offset`, like you would when eyeballing the output
+/// and spotting a word that is almost right.
+#[allow(dead_code)]
+pub fn refine_keystream(keystream: &mut Vec<u8>, ciphertext: &[u8], offset: usize, plaintext: &[u8]) {
+    let end = offset + plaintext.len();
+    if keystream.len() < end {
+        keystream.resize(end, 0);
+    }
+    keystream[offset..end].copy_from_slice(&fixed_xor(&ciphertext[offset..end], plaintext));
+}
+
+#[test]
+fn break_fixed_nonce_test() {
+    let lines: [&[u8]; 20] = [
+        b"I have met them at close of day",
+        b"Coming with vivid faces",
+        b"From counter or desk among grey",
+        b"Eighteenth-century houses.",
+        b"I have passed with a nod of the head",
+        b"Or polite meaningless words,",
+        b"Or have lingered awhile and said",
+        b"Polite meaningless words,",
+        b"And thought before I had done",
+        b"Of a mocking tale or a gibe",
+        b"To please a companion",
+        b"Around the fire at the club,",
+        b"Being certain that they and I",
+        b"But lived where motley is worn:",
+        b"All changed, changed utterly:",
+        b"A terrible beauty is born.",
+        b"That woman's days were spent",
+        b"In ignorant good will,",
+        b"Her nights in argument",
+        b"Until her voice grew shrill.",
+    ];
+    let mut rng = Rng::new(25);
+    let ctr = Ctr::new(Aes::new(&rng.bytes(16)), Counter::LittleEndian { nonce: 0 });
+    let ciphertexts: Vec<Vec<u8>> = lines.iter().map(|line| ctr.apply(0, line)).collect();
+    let expected = ctr.keystream(0, 36);
+    let wrong = |keystream: &[u8]| -> Vec<usize> {
+        (0..keystream.len()).filter(|&i| keystream[i] != expected[i]).collect()
+    };
+
+    // Columns of mostly lowercase letters still get a few bytes wrong,
+    // like the capitals at the start of each line.
+    let keystream = break_fixed_nonce(&ciphertexts);
+    assert_eq!(keystream.len(), 21);
+    assert_eq!(vec![0, 8, 19], wrong(&keystream));
+
+    // The next few columns still have enough samples to go by.
+    let mut keystream = extend_keystream(&keystream, &ciphertexts, 12);
+    assert_eq!(keystream.len(), 28);
+    assert_eq!(vec![0, 8, 19], wrong(&keystream));
+
+    // Fix the rest by hand from guesses at the plaintext.
+    for &i in &[0, 8, 19] {
+        refine_keystream(&mut keystream, &ciphertexts[0], i, &lines[0][i..=i]);
+    }
+    refine_keystream(&mut keystream, &ciphertexts[4], 28, &lines[4][28..]);
+    assert_eq!(keystream, expected);
+    for (ciphertext, line) in ciphertexts.iter().zip(&lines) {
+        assert_eq!(&fixed_xor(ciphertext, &keystream[..ciphertext.len()])[..], *line);
+    }
+}
+
+#[test]
+fn refine_keystream_test() {
+    let mut keystream = vec![0; 2];
+    refine_keystream(&mut keystream, &[1, 2, 3, 4], 1, &[3, 3, 3]);
+    assert_eq!(keystream, vec![0, 1, 0, 7]);
+}
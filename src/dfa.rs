@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use crate::aes::{invert_key_schedule, Aes, Step, INV_S_BOX};
+use crate::gf256;
+
+// Differential fault analysis on AES-128, after Piret and Quisquater.
+// A single byte fault right before the MixColumns of round 9 spreads
+// to one full column, where the differences are the fault times the
+// MixColumns coefficients for the faulted row. The last round has no
+// MixColumns, so those four bytes end up in four known ciphertext
+// positions, each only behind SubBytes and one byte of the last round
+// key. For every possible row and fault value we can check which key
+// bytes explain the observed ciphertext differences, which narrows
+// each column of the last round key down to a few hundred candidates
+// per pair, and two pairs usually agree on just one.
+
+const MIX_COLUMNS: [[u8; 4]; 4] = [
+    [2, 3, 1, 1],
+    [1, 2, 3, 1],
+    [1, 1, 2, 3],
+    [3, 1, 1, 2],
+];
+
+/// Encrypts with AES-128, flipping `fault` into the state byte at
+/// `position` (in natural byte order) just before the MixColumns of
+/// round 9.
+#[allow(dead_code)]
+pub fn faulty_encrypt(aes: &Aes, block: &[u8], position: usize, fault: u8) -> Vec<u8> {
+    aes.encrypt_instrumented(block, 10, |round, step, state| {
+        if round == 9 && step == Step::ShiftRows {
+            state[position] ^= fault;
+        }
+    })
+}
+
+fn ciphertext_positions(column: usize) -> [usize; 4] {
+    // Row i of the column gets moved to column - i by the last
+    // ShiftRows.
+    let mut positions = [0; 4];
+    for (row, position) in positions.iter_mut().enumerate() {
+        *position = (column + 4 - row) % 4 * 4 + row;
+    }
+    positions
+}
+
+fn faulted_column(correct: &[u8], faulty: &[u8]) -> Option<usize> {
+    let differences: Vec<usize> = (0..16)
+        .filter(|&i| correct[i] != faulty[i])
+        .collect();
+    (0..4).find(|&column| {
+        let mut positions = ciphertext_positions(column).to_vec();
+        positions.sort_unstable();
+        positions == differences
+    })
+}
+
+fn column_candidates(correct: &[u8], faulty: &[u8], column: usize) -> HashSet<[u8; 4]> {
+    let positions = ciphertext_positions(column);
+    // For each byte of the column, which key bytes give which
+    // difference going back through the last SubBytes.
+    let mut by_difference = vec![vec![vec![]; 256]; 4];
+    for (i, &position) in positions.iter().enumerate() {
+        for k in 0..=255u8 {
+            let difference = INV_S_BOX[(correct[position] ^ k) as usize]
+                ^ INV_S_BOX[(faulty[position] ^ k) as usize];
+            by_difference[i][difference as usize].push(k);
+        }
+    }
+    let mut candidates = HashSet::new();
+    for row in 0..4 {
+        for fault in 1..=255 {
+            let keys: Vec<&Vec<u8>> = (0..4)
+                .map(|i| &by_difference[i][gf256::mul(MIX_COLUMNS[i][row], fault) as usize])
+                .collect();
+            for &k0 in keys[0] {
+                for &k1 in keys[1] {
+                    for &k2 in keys[2] {
+                        for &k3 in keys[3] {
+                            candidates.insert([k0, k1, k2, k3]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// Recovers the last round key from pairs of correct and faulty
+/// ciphertexts of the same plaintext. Needs pairs for every column,
+/// and returns `None` if they don't narrow it down to a single key.
+pub fn recover_last_round_key(pairs: &[(Vec<u8>, Vec<u8>)]) -> Option<Vec<u8>> {
+    let mut columns: Vec<Option<HashSet<[u8; 4]>>> = vec![None; 4];
+    for (correct, faulty) in pairs {
+        let column = match faulted_column(correct, faulty) {
+            Some(column) => column,
+            // Faults elsewhere don't have the expected pattern.
+            None => continue,
+        };
+        let candidates = column_candidates(correct, faulty, column);
+        columns[column] = Some(match columns[column].take() {
+            Some(previous) => previous.intersection(&candidates).cloned().collect(),
+            None => candidates,
+        });
+    }
+    let mut key = vec![0; 16];
+    for (column, candidates) in columns.iter().enumerate() {
+        let candidates = candidates.as_ref()?;
+        if candidates.len() != 1 {
+            return None;
+        }
+        let bytes = candidates.iter().next()?;
+        for (&position, &byte) in ciphertext_positions(column).iter().zip(bytes) {
+            key[position] = byte;
+        }
+    }
+    Some(key)
+}
+
+/// Recovers the AES-128 key from pairs of correct and faulty
+/// ciphertexts.
+#[allow(dead_code)]
+pub fn recover_key(pairs: &[(Vec<u8>, Vec<u8>)]) -> Option<Vec<u8>> {
+    recover_last_round_key(pairs).map(|key| invert_key_schedule(&key, 10))
+}
+
+#[test]
+fn ciphertext_positions_test() {
+    // A fault in column 0 before MixColumns shows up in ciphertext
+    // bytes 0, 7, 10 and 13.
+    use crate::cipher::BlockCipher;
+    let aes = Aes::new(b"YELLOW SUBMARINE");
+    let block = b"YELLOW SUBMARINE";
+    let correct = aes.encrypt_block(block);
+    let faulty = faulty_encrypt(&aes, block, 2, 0x42);
+    let differences: Vec<usize> = (0..16).filter(|&i| correct[i] != faulty[i]).collect();
+    assert_eq!(vec![0, 7, 10, 13], differences);
+    assert_eq!(Some(0), faulted_column(&correct, &faulty));
+}
+
+#[test]
+fn dfa_test() {
+    use crate::cipher::BlockCipher;
+    use crate::util::Rng;
+    let mut rng = Rng::new(17);
+    let key = rng.bytes(16);
+    let aes = Aes::new(&key);
+    let mut pairs = vec![];
+    for column in 0..4 {
+        for _ in 0..2 {
+            let block = rng.bytes(16);
+            let row = rng.next_u64() as usize % 4;
+            let fault = rng.next_u64() as u8 | 1;
+            pairs.push((
+                aes.encrypt_block(&block),
+                faulty_encrypt(&aes, &block, column * 4 + row, fault),
+            ));
+        }
+    }
+    assert_eq!(Some(key), recover_key(&pairs));
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+
+use crate::aes::Aes;
+use crate::modes::ecb_encrypt;
+use crate::util::Rng;
+
+/// Counts blocks that are repeats of an earlier block. ECB encrypts
+/// identical plaintext blocks to identical ciphertext blocks, so this
+/// is rarely above zero for anything else.
+pub fn repeated_blocks(data: &[u8], block_size: usize) -> usize {
+    let mut seen = HashSet::new();
+    data.chunks(block_size)
+        .filter(|&block| !seen.insert(block))
+        .count()
+}
+
+#[test]
+fn repeated_blocks_test() {
+    assert_eq!(0, repeated_blocks(b"YELLOW SUBMARINE", 4));
+    assert_eq!(2, repeated_blocks(b"ABCDABCDEFGHABCD", 4));
+    assert_eq!(0, repeated_blocks(b"ABCDABCDEFGHABCD", 8));
+}
+
+/// Scores data by repeated blocks for each of the given block sizes,
+/// taking the best one.
+pub fn ecb_score(data: &[u8], block_sizes: &[usize]) -> usize {
+    block_sizes
+        .iter()
+        .map(|&block_size| repeated_blocks(data, block_size))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Sorts candidate indices from most to least likely to be ECB.
+pub fn rank_ecb(candidates: &[Vec<u8>], block_sizes: &[usize]) -> Vec<usize> {
+    let mut ranking: Vec<usize> = (0..candidates.len()).collect();
+    ranking.sort_by_key(|&i| std::cmp::Reverse(ecb_score(&candidates[i], block_sizes)));
+    ranking
+}
+
+#[allow(dead_code)]
+pub fn best_ecb<'a>(candidates: &'a [Vec<u8>], block_sizes: &[usize]) -> &'a [u8] {
+    &candidates[rank_ecb(candidates, block_sizes)[0]]
+}
+
+#[test]
+fn rank_ecb_test() {
+    use crate::modes::cbc_encrypt;
+    let mut rng = Rng::new(18);
+    let aes = Aes::new(&rng.bytes(16));
+    let plaintext = [b'A'; 64];
+    let mut candidates: Vec<Vec<u8>> = (0..10).map(|_| rng.bytes(80)).collect();
+    candidates.push(cbc_encrypt(&aes, &rng.bytes(16), &plaintext));
+    candidates.push(ecb_encrypt(&aes, &plaintext));
+    let ranking = rank_ecb(&candidates, &[16]);
+    assert_eq!(11, ranking[0]);
+    assert_eq!(3, ecb_score(&candidates[11], &[16]));
+    // Half blocks of the same plaintext repeat as well.
+    assert_eq!(6, ecb_score(&candidates[11], &[8, 16]));
+    assert_eq!(&candidates[11][..], best_ecb(&candidates, &[16]));
+}
+
+/// A chosen-plaintext oracle which encrypts
+/// `prefix || input || secret` under AES-ECB with a fixed key.
+#[allow(dead_code)]
+pub struct EcbOracle {
+    aes: Aes,
+    prefix: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl EcbOracle {
+    /// Makes up a random key, and a random prefix of up to
+    /// `max_prefix_len` bytes.
+    pub fn new(rng: &mut Rng, secret: &[u8], max_prefix_len: usize) -> Self {
+        let aes = Aes::new(&rng.bytes(16));
+        let prefix_len = rng.next_u64() as usize % (max_prefix_len + 1);
+        let prefix = rng.bytes(prefix_len);
+        EcbOracle { aes, prefix, secret: secret.to_vec() }
+    }
+
+    pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        let plaintext: Vec<u8> = self.prefix
+            .iter()
+            .chain(input)
+            .chain(&self.secret)
+            .cloned()
+            .collect();
+        ecb_encrypt(&self.aes, &plaintext)
+    }
+}
+
+/// Finds the block size by feeding the oracle more and more bytes
+/// until the ciphertext grows, which it does by one block at a time.
+pub fn detect_block_size<F>(oracle: &F) -> usize
+where F: Fn(&[u8]) -> Vec<u8> {
+    let initial_len = oracle(&[]).len();
+    (1..)
+        .map(|n| oracle(&vec![b'A'; n]).len())
+        .find(|&len| len > initial_len)
+        .unwrap()
+        - initial_len
+}
+
+fn prefix_len_candidates<F>(oracle: &F, block_size: usize, padding: usize, filler: u8) -> Vec<(usize, Vec<u8>)>
+where F: Fn(&[u8]) -> Vec<u8> {
+    // Two identical blocks in a row could mean that the filler after
+    // the padding lines up with a block boundary there. Keep the
+    // repeated block around to tell which input it came from.
+    let ciphertext = oracle(&vec![filler; padding + 2 * block_size]);
+    let blocks: Vec<&[u8]> = ciphertext.chunks(block_size).collect();
+    (0..blocks.len() - 1)
+        .filter(|&i| blocks[i] == blocks[i+1] && padding <= i * block_size)
+        .map(|i| (i * block_size - padding, blocks[i].to_vec()))
+        .collect()
+}
+
+/// Finds the length of a constant prefix the oracle puts in front of
+/// our input.
+pub fn detect_prefix_len<F>(oracle: &F, block_size: usize) -> usize
+where F: Fn(&[u8]) -> Vec<u8> {
+    // Keep adding filler until we get two identical blocks in a row.
+    // The amount of filler needed to line up tells us how far into its
+    // block the prefix ends. A prefix ending in filler bytes or a secret
+    // starting with them can line up one early, so only trust a length
+    // that two different fillers agree on. Repeated blocks in the
+    // secret show up for both fillers as well, but unlike our own
+    // blocks they encrypt the same either way.
+    for padding in 0..block_size {
+        let a = prefix_len_candidates(oracle, block_size, padding, b'A');
+        let b = prefix_len_candidates(oracle, block_size, padding, b'B');
+        let agreed = a.iter().find(|(len, block)| {
+            b.iter().any(|(other_len, other_block)| len == other_len && block != other_block)
+        });
+        if let Some(&(len, _)) = agreed {
+            return len;
+        }
+    }
+    panic!("Oracle is not using ECB")
+}
+
+/// Decrypts whatever the oracle appends to our input, one byte at a
+/// time. We line up the input so that exactly one unknown byte ends up
+/// at the end of a block, and then try all 256 possible values for it
+/// until we find the same ciphertext block.
+#[allow(dead_code)]
+pub fn byte_at_a_time<F>(oracle: F) -> Vec<u8>
+where F: Fn(&[u8]) -> Vec<u8> {
+    let block_size = detect_block_size(&oracle);
+    assert!(0 < repeated_blocks(&oracle(&vec![b'A'; 3 * block_size]), block_size),
+            "Oracle is not using ECB");
+    let prefix_len = detect_prefix_len(&oracle, block_size);
+    // Filler to pad the prefix to a whole block, after which our input
+    // starts at `start`.
+    let alignment = (block_size - prefix_len % block_size) % block_size;
+    let start = prefix_len + alignment;
+
+    let initial_len = oracle(&vec![b'A'; alignment]).len();
+    let secret_len = (1..=block_size)
+        .find(|&n| oracle(&vec![b'A'; alignment + n]).len() > initial_len)
+        .map(|n| initial_len - start - n)
+        .unwrap();
+
+    let mut known: Vec<u8> = vec![];
+    for i in 0..secret_len {
+        let filler = vec![b'A'; alignment + block_size - 1 - i % block_size];
+        let target_start = start + i / block_size * block_size;
+        let target = oracle(&filler)[target_start..target_start + block_size].to_vec();
+        // The last block_size - 1 bytes of filler and what we know so
+        // far, followed by the guess.
+        let mut guess: Vec<u8> = vec![b'A'; alignment];
+        let context: Vec<u8> = vec![b'A'; block_size - 1]
+            .into_iter()
+            .chain(known.iter().cloned())
+            .collect();
+        guess.extend(&context[context.len() - (block_size - 1)..]);
+        guess.push(0);
+        let byte = (0..=255)
+            .find(|&b| {
+                guess[alignment + block_size - 1] = b;
+                oracle(&guess)[start..start + block_size] == target[..]
+            })
+            .expect("No byte matched");
+        known.push(byte);
+    }
+    known
+}
+
+#[test]
+fn detect_test() {
+    let mut rng = Rng::new(19);
+    let oracle = EcbOracle::new(&mut rng, b"secret", 40);
+    let encrypt = |input: &[u8]| oracle.encrypt(input);
+    assert_eq!(16, detect_block_size(&encrypt));
+    assert_eq!(oracle.prefix.len(), detect_prefix_len(&encrypt, 16));
+}
+
+#[test]
+fn byte_at_a_time_test() {
+    let secret = b"Rollin' in my 5.0\nWith my rag-top down so my hair can blow\n";
+    let mut rng = Rng::new(12);
+    let oracle = EcbOracle::new(&mut rng, secret, 0);
+    assert_eq!(secret.to_vec(), byte_at_a_time(|input: &[u8]| oracle.encrypt(input)));
+}
+
+#[test]
+fn byte_at_a_time_prefix_test() {
+    let secret = b"The girlies on standby waving just to say hi\n";
+    let mut rng = Rng::new(14);
+    for _ in 0..4 {
+        let oracle = EcbOracle::new(&mut rng, secret, 40);
+        assert_eq!(secret.to_vec(), byte_at_a_time(|input: &[u8]| oracle.encrypt(input)));
+    }
+}
+
+#[test]
+fn byte_at_a_time_filler_test() {
+    // Prefix and secret bordering on our input with filler bytes.
+    let secret = b"Attack at dawn, then retreat";
+    let mut rng = Rng::new(119);
+    for &(len, last) in &[(5, b'x'), (21, b'A'), (31, b'B'), (40, b'A')] {
+        let mut prefix = rng.bytes(len);
+        prefix[len - 1] = last;
+        let oracle = EcbOracle { aes: Aes::new(&rng.bytes(16)), prefix, secret: secret.to_vec() };
+        let encrypt = |input: &[u8]| oracle.encrypt(input);
+        assert_eq!(len, detect_prefix_len(&encrypt, 16));
+        assert_eq!(secret.to_vec(), byte_at_a_time(encrypt));
+    }
+}
+
+#[test]
+fn byte_at_a_time_repeated_secret_test() {
+    // Repeated blocks in the secret look like our own filler lining up.
+    let mut rng = Rng::new(219);
+    for &secret in &[[b'Z'; 64], [b'A'; 64], [b'B'; 64]] {
+        let oracle = EcbOracle { aes: Aes::new(&rng.bytes(16)), prefix: rng.bytes(5), secret: secret.to_vec() };
+        let encrypt = |input: &[u8]| oracle.encrypt(input);
+        assert_eq!(5, detect_prefix_len(&encrypt, 16));
+        assert_eq!(secret.to_vec(), byte_at_a_time(encrypt));
+    }
+}
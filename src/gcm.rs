@@ -0,0 +1,194 @@
+use std::convert::TryInto;
+
+use crate::cipher::BlockCipher;
+use crate::util::fixed_xor;
+
+#[derive(Debug, PartialEq)]
+pub struct AuthenticationError;
+
+/// Multiplication in GF(2^128) as defined for GCM. Blocks are read as
+/// big-endian integers, but GCM numbers bits from the most significant
+/// end, so the reduction polynomial lives at the top and we shift right.
+pub fn gf128_mul(x: u128, y: u128) -> u128 {
+    const R: u128 = 0xe1 << 120;
+    let mut z = 0;
+    let mut v = y;
+    for i in 0..128 {
+        if x >> (127 - i) & 1 == 1 {
+            z ^= v;
+        }
+        v = if v & 1 == 1 { (v >> 1) ^ R } else { v >> 1 };
+    }
+    z
+}
+
+#[test]
+fn gf128_mul_test() {
+    // The multiplicative identity is the block with only the first
+    // (most significant) bit set.
+    let one = 1 << 127;
+    let x = 0x66e94bd4ef8a2c3b884cfa59ca342b2e;
+    let y = 0x0388dace60b6a392f328c2b971b2fe78;
+    assert_eq!(x, gf128_mul(x, one));
+    assert_eq!(gf128_mul(x, y), gf128_mul(y, x));
+    assert_eq!(0, gf128_mul(x, 0));
+}
+
+fn to_block(bytes: &[u8]) -> u128 {
+    // Zero-pads partial blocks on the right.
+    let mut block = [0; 16];
+    block[..bytes.len()].copy_from_slice(bytes);
+    u128::from_be_bytes(block)
+}
+
+/// GHASH over the associated data and ciphertext, each zero-padded to
+/// a whole number of blocks, followed by a block holding both of their
+/// lengths in bits.
+pub fn ghash(h: u128, aad: &[u8], ciphertext: &[u8]) -> u128 {
+    let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+    aad.chunks(16)
+        .chain(ciphertext.chunks(16))
+        .map(to_block)
+        .chain(Some(lengths))
+        .fold(0, |y, block| gf128_mul(y ^ block, h))
+}
+
+#[test]
+fn ghash_test() {
+    use crate::hex::from_hex;
+    // Test case 2 from the GCM specification.
+    let h = 0x66e94bd4ef8a2c3b884cfa59ca342b2e;
+    let ciphertext = from_hex("0388dace60b6a392f328c2b971b2fe78");
+    assert_eq!(0xf38cbb1ad69223dcc3457ae5b6b0f885, ghash(h, &[], &ciphertext));
+}
+
+fn encrypt_block(cipher: &dyn BlockCipher, block: u128) -> u128 {
+    u128::from_be_bytes(cipher.encrypt_block(&block.to_be_bytes()).try_into().unwrap())
+}
+
+fn initial_counter(h: u128, iv: &[u8]) -> u128 {
+    // 96-bit IVs are used as is with a 32-bit counter of 1 appended,
+    // anything else is hashed down to a block first.
+    if iv.len() == 12 {
+        to_block(iv) | 1
+    } else {
+        ghash(h, &[], iv)
+    }
+}
+
+/// Increments only the low 32 bits of a counter block, wrapping
+/// around without carrying into the rest.
+fn inc32(block: u128) -> u128 {
+    (block & !0xffffffff) | (block as u32).wrapping_add(1) as u128
+}
+
+#[test]
+fn inc32_test() {
+    assert_eq!(0x1_00000002, inc32(0x1_00000001));
+    assert_eq!(0x1_00000000, inc32(0x1_ffffffff));
+}
+
+fn gctr(cipher: &dyn BlockCipher, mut counter: u128, input: &[u8]) -> Vec<u8> {
+    input
+        .chunks(16)
+        .flat_map(|chunk| {
+            let keystream = encrypt_block(cipher, counter).to_be_bytes();
+            counter = inc32(counter);
+            fixed_xor(chunk, &keystream)
+        })
+        .collect()
+}
+
+fn compute_tag(cipher: &dyn BlockCipher, h: u128, j0: u128, aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    (encrypt_block(cipher, j0) ^ ghash(h, aad, ciphertext)).to_be_bytes()
+}
+
+/// Encrypts and authenticates `plaintext`, additionally authenticating
+/// `aad`. Returns the ciphertext and the 128-bit tag.
+#[allow(dead_code)]
+pub fn encrypt(cipher: &dyn BlockCipher, iv: &[u8], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, [u8; 16]) {
+    assert_eq!(16, cipher.block_size(), "GCM needs a 128-bit block cipher");
+    let h = encrypt_block(cipher, 0);
+    let j0 = initial_counter(h, iv);
+    let ciphertext = gctr(cipher, inc32(j0), plaintext);
+    let tag = compute_tag(cipher, h, j0, aad, &ciphertext);
+    (ciphertext, tag)
+}
+
+/// Verifies the tag and only then decrypts.
+#[allow(dead_code)]
+pub fn decrypt(cipher: &dyn BlockCipher, iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> Result<Vec<u8>, AuthenticationError> {
+    assert_eq!(16, cipher.block_size(), "GCM needs a 128-bit block cipher");
+    let h = encrypt_block(cipher, 0);
+    let j0 = initial_counter(h, iv);
+    let expected = compute_tag(cipher, h, j0, aad, ciphertext);
+    // Compare without bailing out early on the first mismatch.
+    let difference = expected
+        .iter()
+        .zip(tag)
+        .fold(0, |acc, (a, b)| acc | (a ^ b));
+    if tag.len() != expected.len() || difference != 0 {
+        return Err(AuthenticationError);
+    }
+    Ok(gctr(cipher, inc32(j0), ciphertext))
+}
+
+#[test]
+fn gcm_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // Test cases 1 and 2 from the GCM specification.
+    let aes = Aes::new(&[0; 16]);
+    let iv = [0; 12];
+    assert_eq!((vec![], from_hex("58e2fccefa7e3061367f1d57a4e7455a").try_into().unwrap()),
+               encrypt(&aes, &iv, &[], &[]));
+    let (ciphertext, tag) = encrypt(&aes, &iv, &[], &[0; 16]);
+    assert_eq!(from_hex("0388dace60b6a392f328c2b971b2fe78"), ciphertext);
+    assert_eq!(from_hex("ab6e47d42cec13bdf53a67b21257bddf"), tag.to_vec());
+    assert_eq!(Ok(vec![0; 16]), decrypt(&aes, &iv, &[], &ciphertext, &tag));
+}
+
+#[test]
+fn gcm_aad_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // Test case 4 from the GCM specification.
+    let aes = Aes::new(&from_hex("feffe9928665731c6d6a8f9467308308"));
+    let iv = from_hex("cafebabefacedbaddecaf888");
+    let aad = from_hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+    let plaintext = from_hex(concat!(
+        "d9313225f88406e5a55909c5aff5269a",
+        "86a7a9531534f7da2e4c303d8a318a72",
+        "1c3c0c95956809532fcf0e2449a6b525",
+        "b16aedf5aa0de657ba637b39",
+    ));
+    let expected = from_hex(concat!(
+        "42831ec2217774244b7221b784d0d49c",
+        "e3aa212f2c02a4e035c17e2329aca12e",
+        "21d514b25466931c7d8f6a5aac84aa05",
+        "1ba30b396a0aac973d58e091",
+    ));
+    let (ciphertext, tag) = encrypt(&aes, &iv, &aad, &plaintext);
+    assert_eq!(expected, ciphertext);
+    assert_eq!(from_hex("5bc94fbc3221a5db94fae95ae7121a47"), tag.to_vec());
+    assert_eq!(Ok(plaintext.clone()), decrypt(&aes, &iv, &aad, &ciphertext, &tag));
+
+    let mut tampered = ciphertext.clone();
+    tampered[0] ^= 1;
+    assert_eq!(Err(AuthenticationError), decrypt(&aes, &iv, &aad, &tampered, &tag));
+    assert_eq!(Err(AuthenticationError), decrypt(&aes, &iv, &aad[1..], &ciphertext, &tag));
+
+    // Test case 5 uses the same inputs with a 64-bit IV, which gets
+    // hashed down to the initial counter block.
+    let iv = from_hex("cafebabefacedbad");
+    let expected = from_hex(concat!(
+        "61353b4c2806934a777ff51fa22a4755",
+        "699b2a714fcdc6f83766e5f97b6c7423",
+        "73806900e49f24b22b097544d4896b42",
+        "4989b5e1ebac0f07c23f4598",
+    ));
+    let (ciphertext, tag) = encrypt(&aes, &iv, &aad, &plaintext);
+    assert_eq!(expected, ciphertext);
+    assert_eq!(from_hex("3612d2e79e3b0785561be14aaca2fccb"), tag.to_vec());
+    assert_eq!(Ok(plaintext), decrypt(&aes, &iv, &aad, &ciphertext, &tag));
+}
@@ -0,0 +1,115 @@
+// Arithmetic in GF(2^8) with the AES reduction polynomial
+// x^8 + x^4 + x^3 + x + 1. Everything is a const fn so the tables in
+// the aes module can be derived at compile time.
+
+/// Multiplication, shifting and adding the way it is done on paper,
+/// reducing whenever the top bit would fall off.
+pub const fn mul(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut b = b;
+    let mut result = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            result ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    result
+}
+
+#[test]
+fn mul_test() {
+    // Examples from FIPS-197 section 4.2.
+    assert_eq!(0xc1, mul(0x57, 0x83));
+    assert_eq!(0xfe, mul(0x57, 0x13));
+    assert_eq!(0xae, mul(0x57, 0x02));
+    assert_eq!(0x00, mul(0x57, 0x00));
+}
+
+pub const fn pow(a: u8, n: u32) -> u8 {
+    let mut base = a;
+    let mut n = n;
+    let mut result = 1;
+    while n != 0 {
+        if n & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        n >>= 1;
+    }
+    result
+}
+
+#[test]
+fn pow_test() {
+    assert_eq!(1, pow(0x57, 0));
+    assert_eq!(0x57, pow(0x57, 1));
+    assert_eq!(mul(0x57, mul(0x57, 0x57)), pow(0x57, 3));
+    // The round constants are powers of x.
+    assert_eq!(0x1b, pow(0x02, 8));
+    assert_eq!(0x36, pow(0x02, 9));
+}
+
+/// The multiplicative inverse, with 0 mapping to 0. The multiplicative
+/// group has order 255, so a^254 = a^-1.
+pub const fn inverse(a: u8) -> u8 {
+    pow(a, 254)
+}
+
+#[test]
+fn inverse_test() {
+    assert_eq!(0, inverse(0));
+    assert_eq!(0xca, inverse(0x53));
+    for a in 1..=255 {
+        assert_eq!(1, mul(a, inverse(a)));
+    }
+}
+
+/// The S-box with a custom constant in the affine transformation. AES
+/// uses 0x63.
+pub const fn s_box_with(constant: u8) -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let b = inverse(i as u8);
+        table[i] = b
+            ^ b.rotate_left(1)
+            ^ b.rotate_left(2)
+            ^ b.rotate_left(3)
+            ^ b.rotate_left(4)
+            ^ constant;
+        i += 1;
+    }
+    table
+}
+
+pub const fn s_box() -> [u8; 256] {
+    s_box_with(0x63)
+}
+
+/// Inverts a table that is a permutation, such as an S-box.
+pub const fn invert_table(table: &[u8; 256]) -> [u8; 256] {
+    let mut inverse = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inverse[table[i] as usize] = i as u8;
+        i += 1;
+    }
+    inverse
+}
+
+/// Multiplication by a fixed factor for every possible byte.
+pub const fn mul_table(factor: u8) -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = mul(i as u8, factor);
+        i += 1;
+    }
+    table
+}
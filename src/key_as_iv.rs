@@ -0,0 +1,74 @@
+use crate::aes::Aes;
+use crate::modes::{cbc_decrypt_blocks, cbc_encrypt};
+use crate::pkcs7::{unpad, PaddingError};
+use crate::util::{fixed_xor, Rng};
+
+#[derive(Debug, PartialEq)]
+pub enum DecryptError {
+    Padding(PaddingError),
+    /// The plaintext has bytes outside of 7-bit ASCII. Helpfully
+    /// includes the offending plaintext.
+    HighAscii(Vec<u8>),
+}
+
+/// Encrypts under AES-CBC, using the key as the IV because it saves
+/// having to send an IV.
+#[allow(dead_code)]
+pub struct KeyAsIvOracle {
+    aes: Aes,
+    key: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl KeyAsIvOracle {
+    pub fn new(rng: &mut Rng) -> Self {
+        let key = rng.bytes(16);
+        KeyAsIvOracle { aes: Aes::new(&key), key }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        cbc_encrypt(&self.aes, &self.key, plaintext)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
+            return Err(DecryptError::Padding(PaddingError));
+        }
+        let plaintext = cbc_decrypt_blocks(&self.aes, &self.key, ciphertext);
+        if plaintext.iter().any(|&b| b > 0x7f) {
+            return Err(DecryptError::HighAscii(plaintext));
+        }
+        unpad(&plaintext, 16).map_err(DecryptError::Padding)
+    }
+}
+
+/// Recovers the key, given a ciphertext of at least one block. We
+/// send C1 || 0 || C1, which decrypts to P1 = D(C1) ^ key, garbage,
+/// and P3 = D(C1) ^ 0, so P1 ^ P3 is the key, provided the oracle
+/// complains about the plaintext and shows it to us.
+#[allow(dead_code)]
+pub fn recover_key<F>(ciphertext: &[u8], decrypt: F) -> Option<Vec<u8>>
+where F: Fn(&[u8]) -> Result<Vec<u8>, DecryptError> {
+    assert!(16 <= ciphertext.len(), "Need at least one block");
+    let first = &ciphertext[..16];
+    let forged: Vec<u8> = first.iter().chain(&[0; 16]).chain(first).cloned().collect();
+    match decrypt(&forged) {
+        Err(DecryptError::HighAscii(plaintext)) => Some(fixed_xor(&plaintext[..16], &plaintext[32..48])),
+        _ => None,
+    }
+}
+
+#[test]
+fn recover_key_test() {
+    let oracle = KeyAsIvOracle::new(&mut Rng::new(24));
+    let plaintext = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+    let ciphertext = oracle.encrypt(plaintext);
+    assert_eq!(Ok(plaintext.to_vec()), oracle.decrypt(&ciphertext));
+    let key = recover_key(&ciphertext, |c| oracle.decrypt(c)).unwrap();
+    assert_eq!(oracle.key, key);
+    // With the key we can decrypt anything.
+    use crate::modes::cbc_decrypt;
+    assert_eq!(Ok(plaintext.to_vec()), cbc_decrypt(&Aes::new(&key), &key, &ciphertext));
+    // A single block is enough.
+    assert_eq!(Some(key), recover_key(&oracle.encrypt(b"hi"), |c| oracle.decrypt(c)));
+}
@@ -1,64 +1,35 @@
-use std::collections::HashMap;
-use std::convert::TryInto;
 use std::fs;
 use std::iter::repeat;
 
 mod aes;
+mod aes_bitsliced;
+mod aes_ttable;
 mod base64;
+mod bitflip;
+mod cipher;
+mod ctr;
+mod ctr_attacks;
+mod dfa;
+mod ecb;
+mod gcm;
+mod gf256;
 mod hex;
+mod key_as_iv;
+mod modes;
+mod padding_oracle;
+mod pkcs7;
+mod profile;
+mod square;
 mod util;
 
-use aes::{build_state, decrypt};
+use aes::Aes;
+use aes_ttable::AesTTable;
 use base64::{from_base64, to_base64};
+use ctr::{Counter, Ctr};
 use hex::{from_hex, to_hex};
-use util::{fixed_xor, hamming_distance, transpose};
-
-// TODO
-// Should probably try to improve performance of score_string.
-// Might be a good idea to figure out how to profile Rust.
-
-fn score_string(s: &[u8]) -> usize {
-    let expected: HashMap<u8, i32> = [
-        (b' ', 130000),
-        (b'E', 111607), (b'A', 84966), (b'R', 75809), (b'I', 75448),
-        (b'O', 71635), (b'T', 69509), (b'N', 66544), (b'S', 57351),
-        (b'L', 54893), (b'C', 45388), (b'U', 36308), (b'D', 33844),
-        (b'P', 31671), (b'M', 30129), (b'H', 30034), (b'G', 24705),
-        (b'B', 20720), (b'F', 18121), (b'Y', 17779), (b'W', 12899),
-        (b'K', 11016), (b'V', 10074), (b'X', 02902), (b'Z', 02722),
-        (b'J', 01965), (b'Q', 01962),
-    ].iter().cloned().collect();
-
-    let mut char_counts: HashMap<u8, usize> = HashMap::new();
-
-    let mut non_ascii = 0;
-
-    for c in s {
-        if c.is_ascii_alphanumeric() || c.is_ascii_whitespace() {
-            let key = c.to_ascii_uppercase();
-            match char_counts.get_mut(&key) {
-                Some(count) => *count += 1,
-                None => {
-                    char_counts.insert(key, 1);
-                    ()
-                },
-            }
-        } else {
-            non_ascii += 1;
-        }
-    }
-
-    let diff: i32 = expected
-        .iter()
-        .map(|(c, expectation)| {
-            let &actual = char_counts.get(c).unwrap_or(&0);
-            let rate: i32 = 100 * actual as i32 / s.len() as i32;
-            (rate - expectation).abs()
-        })
-        .sum();
-
-    diff as usize * 100 + non_ascii * 100
-}
+use modes::ecb_decrypt;
+use pkcs7::pad;
+use util::{fixed_xor, hamming_distance, score_string, single_byte_xor_key, transpose};
 
 fn best_string<'a>(strings: &'a Vec<Vec<u8>>) -> &'a[u8] {
     strings
@@ -151,19 +122,7 @@ fn s1c6() {
 
     let key: Vec<u8> = transpose(chunks, b' ')
         .iter()
-        .map(|chunk| {
-            let mut results = vec![];
-            for k in 0..128 {
-                let key: Vec<u8> = repeat(k as u8)
-                    .take(chunk.len())
-                    .collect();
-                results.push((k, fixed_xor(&chunk, &key)));
-            }
-            results.iter()
-                .min_by_key(|(_, result)| score_string(result))
-                .unwrap()
-                .0
-        })
+        .map(|chunk| single_byte_xor_key(chunk))
         .collect();
 
     let repeated_key: Vec<u8> = key
@@ -182,23 +141,25 @@ fn s1c7() {
     let raw_input = fs::read_to_string("resources/7.txt")
         .expect("Failed to read 7.txt");
     let input = from_base64(&raw_input);
-    let key = b"YELLOW SUBMARINE"
-        .chunks(4)
-        .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
-        .collect::<Vec<u32>>()
-        .as_slice()
-        .try_into()
-        .unwrap();
-    let decrypted: Vec<u8> = input
-        .chunks(16)
-        .flat_map(|chunk| {
-            let transposed = decrypt(build_state(chunk), key);
-            build_state(&transposed[..]).to_vec()
-        })
-        .collect();
+    let aes = AesTTable::new(b"YELLOW SUBMARINE");
+    let decrypted = ecb_decrypt(&aes, &input)
+        .expect("Invalid padding");
     println!("1-7: {}", String::from_utf8(decrypted).unwrap());
 }
 
+fn s2c9() {
+    // Set 2 - Challenge 9
+    let padded = pad(b"YELLOW SUBMARINE", 20);
+    println!("2-9: {:?}", String::from_utf8(padded).unwrap());
+}
+
+fn s3c18() {
+    // Set 3 - Challenge 18
+    let input = from_base64("L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==");
+    let ctr = Ctr::new(Aes::new(b"YELLOW SUBMARINE"), Counter::LittleEndian { nonce: 0 });
+    println!("3-18: {}", String::from_utf8(ctr.apply(0, &input)).unwrap());
+}
+
 fn main() {
     s1c1();
     s1c2();
@@ -207,4 +168,6 @@ fn main() {
     s1c5();
     s1c6();
     s1c7();
+    s2c9();
+    s3c18();
 }
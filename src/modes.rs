@@ -0,0 +1,244 @@
+use std::iter::once;
+
+use crate::cipher::BlockCipher;
+use crate::pkcs7::{pad, unpad, PaddingError};
+use crate::util::fixed_xor;
+
+#[allow(dead_code)]
+pub fn ecb_encrypt(cipher: &dyn BlockCipher, input: &[u8]) -> Vec<u8> {
+    let block_size = cipher.block_size();
+    pad(input, block_size)
+        .chunks(block_size)
+        .flat_map(|block| cipher.encrypt_block(block))
+        .collect()
+}
+
+pub fn ecb_decrypt(cipher: &dyn BlockCipher, input: &[u8]) -> Result<Vec<u8>, PaddingError> {
+    let block_size = cipher.block_size();
+    if !input.len().is_multiple_of(block_size) {
+        return Err(PaddingError);
+    }
+    let decrypted: Vec<u8> = input
+        .chunks(block_size)
+        .flat_map(|block| cipher.decrypt_block(block))
+        .collect();
+    unpad(&decrypted, block_size)
+}
+
+#[test]
+fn ecb_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // NIST SP 800-38A F.1.1, plus a trailing block of padding.
+    let aes = Aes::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let input = from_hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+    let encrypted = ecb_encrypt(&aes, &input);
+    assert_eq!(48, encrypted.len());
+    assert_eq!(from_hex("3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf"),
+               encrypted[..32].to_vec());
+    assert_eq!(Ok(input), ecb_decrypt(&aes, &encrypted));
+}
+
+#[test]
+fn ecb_unaligned_test() {
+    use crate::aes::Aes;
+    let aes = Aes::new(b"YELLOW SUBMARINE");
+    let input = b"We all live in a yellow submarine";
+    let encrypted = ecb_encrypt(&aes, input);
+    assert_eq!(48, encrypted.len());
+    assert_eq!(Ok(input.to_vec()), ecb_decrypt(&aes, &encrypted));
+    assert_eq!(Err(PaddingError), ecb_decrypt(&aes, &encrypted[..40]));
+}
+
+pub fn cbc_encrypt_blocks(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    // Unpadded, so the input has to be a whole number of blocks.
+    let block_size = cipher.block_size();
+    assert_eq!(block_size, iv.len(), "Invalid IV length");
+    assert!(input.len().is_multiple_of(block_size), "Input is not block aligned");
+    let mut previous = iv.to_vec();
+    let mut result = Vec::with_capacity(input.len());
+    for block in input.chunks(block_size) {
+        previous = cipher.encrypt_block(&fixed_xor(block, &previous));
+        result.extend(&previous);
+    }
+    result
+}
+
+pub fn cbc_decrypt_blocks(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    let block_size = cipher.block_size();
+    assert_eq!(block_size, iv.len(), "Invalid IV length");
+    assert!(input.len().is_multiple_of(block_size), "Input is not block aligned");
+    once(iv)
+        .chain(input.chunks(block_size))
+        .zip(input.chunks(block_size))
+        .flat_map(|(previous, block)| fixed_xor(&cipher.decrypt_block(block), previous))
+        .collect()
+}
+
+#[test]
+fn cbc_blocks_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // NIST SP 800-38A F.2.1 and F.2.2.
+    let aes = Aes::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+    let plaintext = from_hex(concat!(
+        "6bc1bee22e409f96e93d7e117393172a",
+        "ae2d8a571e03ac9c9eb76fac45af8e51",
+        "30c81c46a35ce411e5fbc1191a0a52ef",
+        "f69f2445df4f9b17ad2b417be66c3710",
+    ));
+    let ciphertext = from_hex(concat!(
+        "7649abac8119b246cee98e9b12e9197d",
+        "5086cb9b507219ee95db113a917678b2",
+        "73bed6b8e3c1743b7116e69e22229516",
+        "3ff1caa1681fac09120eca307586e1a7",
+    ));
+    assert_eq!(ciphertext, cbc_encrypt_blocks(&aes, &iv, &plaintext));
+    assert_eq!(plaintext, cbc_decrypt_blocks(&aes, &iv, &ciphertext));
+}
+
+#[allow(dead_code)]
+pub fn cbc_encrypt(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    cbc_encrypt_blocks(cipher, iv, &pad(input, cipher.block_size()))
+}
+
+#[allow(dead_code)]
+pub fn cbc_decrypt(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Result<Vec<u8>, PaddingError> {
+    let block_size = cipher.block_size();
+    if input.is_empty() || !input.len().is_multiple_of(block_size) {
+        return Err(PaddingError);
+    }
+    unpad(&cbc_decrypt_blocks(cipher, iv, input), block_size)
+}
+
+#[test]
+fn cbc_test() {
+    use crate::aes::Aes;
+    let aes = Aes::new(b"YELLOW SUBMARINE");
+    let iv = [0; 16];
+    let input = b"We all live in a yellow submarine";
+    let encrypted = cbc_encrypt(&aes, &iv, input);
+    assert_eq!(48, encrypted.len());
+    assert_eq!(Ok(input.to_vec()), cbc_decrypt(&aes, &iv, &encrypted));
+    // Identical plaintext blocks must not give identical ciphertext blocks.
+    let encrypted = cbc_encrypt(&aes, &iv, &[b'A'; 32]);
+    assert_ne!(encrypted[..16], encrypted[16..32]);
+    assert_eq!(Err(PaddingError), cbc_decrypt(&aes, &iv, &encrypted[..40]));
+}
+
+#[allow(dead_code)]
+pub fn ofb(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    // The keystream is the IV encrypted over and over, independent of
+    // the data, so the same function encrypts and decrypts.
+    let block_size = cipher.block_size();
+    assert_eq!(block_size, iv.len(), "Invalid IV length");
+    let mut register = iv.to_vec();
+    input
+        .chunks(block_size)
+        .flat_map(|chunk| {
+            register = cipher.encrypt_block(&register);
+            fixed_xor(chunk, &register)
+        })
+        .collect()
+}
+
+fn cfb(cipher: &dyn BlockCipher, iv: &[u8], segment_size: usize, input: &[u8], decrypt: bool) -> Vec<u8> {
+    // The shift register starts out as the IV, and after each segment
+    // the ciphertext segment gets shifted in from the right.
+    let block_size = cipher.block_size();
+    assert_eq!(block_size, iv.len(), "Invalid IV length");
+    assert!(segment_size <= block_size, "Segment size larger than the block size");
+    let mut register = iv.to_vec();
+    let mut result = Vec::with_capacity(input.len());
+    for segment in input.chunks(segment_size) {
+        let output = fixed_xor(segment, &cipher.encrypt_block(&register));
+        let ciphertext = if decrypt { segment } else { &output };
+        register.drain(..ciphertext.len());
+        register.extend(ciphertext);
+        result.extend(output);
+    }
+    result
+}
+
+#[allow(dead_code)]
+pub fn cfb8_encrypt(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    cfb(cipher, iv, 1, input, false)
+}
+
+#[allow(dead_code)]
+pub fn cfb8_decrypt(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    cfb(cipher, iv, 1, input, true)
+}
+
+#[allow(dead_code)]
+pub fn cfb128_encrypt(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    cfb(cipher, iv, 16, input, false)
+}
+
+#[allow(dead_code)]
+pub fn cfb128_decrypt(cipher: &dyn BlockCipher, iv: &[u8], input: &[u8]) -> Vec<u8> {
+    cfb(cipher, iv, 16, input, true)
+}
+
+#[test]
+fn ofb_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // NIST SP 800-38A F.4.1 and F.4.2.
+    let aes = Aes::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+    let plaintext = from_hex(concat!(
+        "6bc1bee22e409f96e93d7e117393172a",
+        "ae2d8a571e03ac9c9eb76fac45af8e51",
+        "30c81c46a35ce411e5fbc1191a0a52ef",
+        "f69f2445df4f9b17ad2b417be66c3710",
+    ));
+    let ciphertext = from_hex(concat!(
+        "3b3fd92eb72dad20333449f8e83cfb4a",
+        "7789508d16918f03f53c52dac54ed825",
+        "9740051e9c5fecf64344f7a82260edcc",
+        "304c6528f659c77866a510d9c1d6ae5e",
+    ));
+    assert_eq!(ciphertext, ofb(&aes, &iv, &plaintext));
+    assert_eq!(plaintext, ofb(&aes, &iv, &ciphertext));
+    // Truncated messages just use less of the keystream.
+    assert_eq!(plaintext[..21].to_vec(), ofb(&aes, &iv, &ciphertext[..21]));
+}
+
+#[test]
+fn cfb8_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // NIST SP 800-38A F.3.7 and F.3.8.
+    let aes = Aes::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+    let plaintext = from_hex("6bc1bee22e409f96e93d7e117393172aae2d");
+    let ciphertext = from_hex("3b79424c9c0dd436bace9e0ed4586a4f32b9");
+    assert_eq!(ciphertext, cfb8_encrypt(&aes, &iv, &plaintext));
+    assert_eq!(plaintext, cfb8_decrypt(&aes, &iv, &ciphertext));
+}
+
+#[test]
+fn cfb128_test() {
+    use crate::aes::Aes;
+    use crate::hex::from_hex;
+    // NIST SP 800-38A F.3.13 and F.3.14.
+    let aes = Aes::new(&from_hex("2b7e151628aed2a6abf7158809cf4f3c"));
+    let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+    let plaintext = from_hex(concat!(
+        "6bc1bee22e409f96e93d7e117393172a",
+        "ae2d8a571e03ac9c9eb76fac45af8e51",
+        "30c81c46a35ce411e5fbc1191a0a52ef",
+        "f69f2445df4f9b17ad2b417be66c3710",
+    ));
+    let ciphertext = from_hex(concat!(
+        "3b3fd92eb72dad20333449f8e83cfb4a",
+        "c8a64537a0b3a93fcde3cdad9f1ce58b",
+        "26751f67a3cbb140b1808cf187a4f4df",
+        "c04b05357c5d1c0eeac4c66f9ff7f2e6",
+    ));
+    assert_eq!(ciphertext, cfb128_encrypt(&aes, &iv, &plaintext));
+    assert_eq!(plaintext, cfb128_decrypt(&aes, &iv, &ciphertext));
+    assert_eq!(plaintext[..50].to_vec(), cfb128_decrypt(&aes, &iv, &ciphertext[..50]));
+}
@@ -0,0 +1,144 @@
+use crate::aes::Aes;
+use crate::modes::{cbc_decrypt, cbc_encrypt};
+use crate::pkcs7::{pad, unpad, PaddingError};
+use crate::util::{fixed_xor, Rng};
+
+// The CBC padding oracle attack. Decrypting a block in CBC is D(C) xor
+// the previous block, and we control the previous block (as the IV).
+// So we can tweak its last byte until the oracle says the padding is
+// valid, at which point the last byte of D(C) xor our byte is almost
+// certainly 0x01. Then we set it up to give 0x02 and go for the byte
+// before, and so on, recovering all of D(C) without the key.
+
+/// Recovers D(block), the block cipher decryption of a single block,
+/// from an oracle that takes an IV and a ciphertext and tells us if
+/// the padding is valid.
+pub fn intermediate<F>(oracle: &F, block: &[u8]) -> Vec<u8>
+where F: Fn(&[u8], &[u8]) -> bool {
+    let block_size = block.len();
+    let mut intermediate = vec![0; block_size];
+    for position in (0..block_size).rev() {
+        let padding = (block_size - position) as u8;
+        let mut iv = vec![0; block_size];
+        for i in position + 1..block_size {
+            iv[i] = intermediate[i] ^ padding;
+        }
+        let guess = (0..=255)
+            .find(|&guess| {
+                iv[position] = guess;
+                if !oracle(&iv, block) {
+                    return false;
+                }
+                // For the last byte, valid padding could also be the
+                // longer padding that happens to be there, like 0x02
+                // 0x02. Changing the byte before tells them apart,
+                // because it only matters for the longer padding.
+                if position == block_size - 1 && 0 < position {
+                    let mut check = iv.clone();
+                    check[position - 1] ^= 1;
+                    return oracle(&check, block);
+                }
+                true
+            })
+            .expect("No valid padding found");
+        intermediate[position] = guess ^ padding;
+    }
+    intermediate
+}
+
+/// Decrypts CBC ciphertext using only a padding oracle.
+#[allow(dead_code)]
+pub fn decrypt<F>(oracle: F, iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, PaddingError>
+where F: Fn(&[u8], &[u8]) -> bool {
+    let block_size = iv.len();
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(block_size) {
+        return Err(PaddingError);
+    }
+    let previous_blocks = std::iter::once(iv).chain(ciphertext.chunks(block_size));
+    let plaintext: Vec<u8> = ciphertext
+        .chunks(block_size)
+        .zip(previous_blocks)
+        .flat_map(|(block, previous)| fixed_xor(&intermediate(&oracle, block), previous))
+        .collect();
+    unpad(&plaintext, block_size)
+}
+
+/// Encrypts arbitrary plaintext using only a padding oracle (CBC-R).
+/// Works backwards from an arbitrary last block: once we know D(C) for
+/// a block, we can choose the block before it to make it decrypt to
+/// whatever we want. Returns the IV and the ciphertext.
+#[allow(dead_code)]
+pub fn encrypt<F>(oracle: F, block_size: usize, plaintext: &[u8]) -> (Vec<u8>, Vec<u8>)
+where F: Fn(&[u8], &[u8]) -> bool {
+    let padded = pad(plaintext, block_size);
+    let mut blocks = vec![vec![0; block_size]];
+    for block in padded.chunks(block_size).rev() {
+        let previous = fixed_xor(&intermediate(&oracle, &blocks[0]), block);
+        blocks.insert(0, previous);
+    }
+    let iv = blocks.remove(0);
+    (iv, blocks.concat())
+}
+
+/// Encrypts with AES-CBC under a random key, and tells us whether
+/// ciphertexts have valid padding, but nothing else.
+#[allow(dead_code)]
+pub struct CbcPaddingOracle {
+    aes: Aes,
+}
+
+#[allow(dead_code)]
+impl CbcPaddingOracle {
+    pub fn new(rng: &mut Rng) -> Self {
+        CbcPaddingOracle { aes: Aes::new(&rng.bytes(16)) }
+    }
+
+    pub fn encrypt(&self, iv: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        cbc_encrypt(&self.aes, iv, plaintext)
+    }
+
+    pub fn padding_valid(&self, iv: &[u8], ciphertext: &[u8]) -> bool {
+        cbc_decrypt(&self.aes, iv, ciphertext).is_ok()
+    }
+
+    pub fn decrypt(&self, iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, PaddingError> {
+        cbc_decrypt(&self.aes, iv, ciphertext)
+    }
+}
+
+#[test]
+fn padding_oracle_decrypt_test() {
+    let mut rng = Rng::new(21);
+    let oracle = CbcPaddingOracle::new(&mut rng);
+    let plaintexts: [&[u8]; 2] = [
+        b"000000Now that the party is jumping",
+        b"exactly sixteen!",
+    ];
+    for plaintext in plaintexts.iter() {
+        let iv = rng.bytes(16);
+        let ciphertext = oracle.encrypt(&iv, plaintext);
+        let recovered = decrypt(|iv: &[u8], c: &[u8]| oracle.padding_valid(iv, c), &iv, &ciphertext);
+        assert_eq!(Ok(plaintext.to_vec()), recovered);
+    }
+}
+
+#[test]
+fn intermediate_ambiguous_padding_test() {
+    // With 0x02 as the second to last byte of D(C), guessing the last
+    // byte also finds valid 0x02 0x02 padding. Try every last byte so
+    // the wrong guess comes up before the right one at least once.
+    let mut decrypted = b"not quite ok\x00\x00\x02\x00".to_vec();
+    for last in 0..=255 {
+        decrypted[15] = last;
+        let oracle = |iv: &[u8], _: &[u8]| unpad(&fixed_xor(&decrypted, iv), 16).is_ok();
+        assert_eq!(decrypted, intermediate(&oracle, &[0; 16]));
+    }
+}
+
+#[test]
+fn padding_oracle_encrypt_test() {
+    let oracle = CbcPaddingOracle::new(&mut Rng::new(21));
+    let plaintext = b"comment1=cooking%20MCs;admin=true";
+    let (iv, ciphertext) = encrypt(|iv: &[u8], c: &[u8]| oracle.padding_valid(iv, c), 16, plaintext);
+    assert_eq!(Ok(plaintext.to_vec()), oracle.decrypt(&iv, &ciphertext));
+}
@@ -0,0 +1,43 @@
+#[derive(Debug, PartialEq)]
+pub struct PaddingError;
+
+pub fn pad(input: &[u8], block_size: usize) -> Vec<u8> {
+    // There is always at least one byte of padding, so a message that
+    // is already aligned gets a whole block of it.
+    let padding = block_size - input.len() % block_size;
+    let mut result = input.to_vec();
+    result.resize(input.len() + padding, padding as u8);
+    result
+}
+
+#[test]
+fn pad_test() {
+    assert_eq!(b"YELLOW SUBMARINE\x04\x04\x04\x04".to_vec(),
+               pad(b"YELLOW SUBMARINE", 20));
+    assert_eq!(32, pad(b"YELLOW SUBMARINE", 16).len());
+}
+
+pub fn unpad(input: &[u8], block_size: usize) -> Result<Vec<u8>, PaddingError> {
+    if input.is_empty() || !input.len().is_multiple_of(block_size) {
+        return Err(PaddingError);
+    }
+    let padding = input[input.len() - 1] as usize;
+    if padding == 0 || padding > block_size {
+        return Err(PaddingError);
+    }
+    let (message, tail) = input.split_at(input.len() - padding);
+    if tail.iter().any(|&b| b as usize != padding) {
+        return Err(PaddingError);
+    }
+    Ok(message.to_vec())
+}
+
+#[test]
+fn unpad_test() {
+    assert_eq!(Ok(b"ICE ICE BABY".to_vec()),
+               unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16));
+    assert_eq!(Err(PaddingError), unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16));
+    assert_eq!(Err(PaddingError), unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16));
+    assert_eq!(Err(PaddingError), unpad(b"ICE ICE BABY\x00\x00\x00\x00", 16));
+    assert_eq!(Err(PaddingError), unpad(b"ICE ICE BABY\x04\x04\x04", 16));
+}
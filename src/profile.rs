@@ -0,0 +1,111 @@
+use crate::aes::Aes;
+use crate::modes::{ecb_decrypt, ecb_encrypt};
+use crate::pkcs7::{pad, PaddingError};
+use crate::util::Rng;
+
+/// Parses `key=value&...` into pairs, keeping their order. Pairs
+/// without an `=` get an empty value.
+pub fn parse_cookie(s: &str) -> Vec<(String, String)> {
+    s.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or("").to_string();
+            let value = parts.next().unwrap_or("").to_string();
+            (key, value)
+        })
+        .collect()
+}
+
+#[test]
+fn parse_cookie_test() {
+    assert_eq!(
+        vec![
+            ("foo".to_string(), "bar".to_string()),
+            ("baz".to_string(), "qux".to_string()),
+            ("zap".to_string(), "zazzle".to_string()),
+        ],
+        parse_cookie("foo=bar&baz=qux&zap=zazzle")
+    );
+    assert_eq!(vec![("flag".to_string(), "".to_string())], parse_cookie("flag"));
+}
+
+/// Encodes pairs as `key=value&...`, dropping any `&` and `=` from
+/// keys and values so they can't inject pairs of their own.
+pub fn encode_cookie(pairs: &[(&str, &str)]) -> String {
+    let clean = |s: &str| s.replace(&['&', '='][..], "");
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", clean(key), clean(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+pub fn profile_for(email: &str) -> String {
+    encode_cookie(&[("email", email), ("uid", "10"), ("role", "user")])
+}
+
+#[test]
+fn profile_for_test() {
+    assert_eq!("email=foo@bar.com&uid=10&role=user", profile_for("foo@bar.com"));
+    assert_eq!("email=foo@bar.comroleadmin&uid=10&role=user",
+               profile_for("foo@bar.com&role=admin"));
+}
+
+/// Hands out ECB-encrypted user profiles for any email address, and
+/// reads back their role.
+#[allow(dead_code)]
+pub struct ProfileOracle {
+    aes: Aes,
+}
+
+#[allow(dead_code)]
+impl ProfileOracle {
+    pub fn new(rng: &mut Rng) -> Self {
+        ProfileOracle { aes: Aes::new(&rng.bytes(16)) }
+    }
+
+    pub fn encrypted_profile_for(&self, email: &str) -> Vec<u8> {
+        ecb_encrypt(&self.aes, profile_for(email).as_bytes())
+    }
+
+    pub fn role(&self, ciphertext: &[u8]) -> Result<Option<String>, PaddingError> {
+        let plaintext = ecb_decrypt(&self.aes, ciphertext)?;
+        let cookie = String::from_utf8_lossy(&plaintext).into_owned();
+        Ok(parse_cookie(&cookie)
+            .into_iter()
+            .find(|(key, _)| key == "role")
+            .map(|(_, value)| value))
+    }
+}
+
+/// Forges a profile with `role=admin` by cutting and pasting blocks of
+/// profiles we are allowed to ask for.
+#[allow(dead_code)]
+pub fn forge_admin<F>(encrypted_profile_for: F) -> Vec<u8>
+where F: Fn(&str) -> Vec<u8> {
+    let prefix_len = "email=".len();
+    // First, an email that puts a block of "admin" plus valid padding
+    // right at the start of the second block.
+    let filler = "A".repeat(16 - prefix_len);
+    let admin = String::from_utf8(pad(b"admin", 16)).unwrap();
+    let admin_block = encrypted_profile_for(&(filler + &admin))[16..32].to_vec();
+    // Then an email long enough to push "user" to the start of the
+    // last block, which we replace with the admin one.
+    let rest_len = "&uid=10&role=".len();
+    let email_len = (32 - (prefix_len + rest_len) % 16) % 16;
+    let email = format!("{}@bar.com", "a".repeat(email_len - "@bar.com".len()));
+    let mut forged = encrypted_profile_for(&email);
+    forged.truncate(forged.len() - 16);
+    forged.extend(admin_block);
+    forged
+}
+
+#[test]
+fn forge_admin_test() {
+    let oracle = ProfileOracle::new(&mut Rng::new(20));
+    let honest = oracle.encrypted_profile_for("foo@bar.com");
+    assert_eq!(Ok(Some("user".to_string())), oracle.role(&honest));
+    let forged = forge_admin(|email| oracle.encrypted_profile_for(email));
+    assert_eq!(Ok(Some("admin".to_string())), oracle.role(&forged));
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 pub fn fixed_xor(a: &[u8], b: &[u8]) -> Vec<u8> {
     a.iter()
         .zip(b)
@@ -31,3 +33,100 @@ pub fn transpose<T>(v: Vec<Vec<T>>, fill: T) -> Vec<Vec<T>> where T: Clone {
              .collect::<Vec<T>>())
         .collect()
 }
+
+// TODO
+// Should probably try to improve performance of score_string.
+// Might be a good idea to figure out how to profile Rust.
+
+pub fn score_string(s: &[u8]) -> usize {
+    let expected: HashMap<u8, i32> = [
+        (b' ', 130000),
+        (b'E', 111607), (b'A', 84966), (b'R', 75809), (b'I', 75448),
+        (b'O', 71635), (b'T', 69509), (b'N', 66544), (b'S', 57351),
+        (b'L', 54893), (b'C', 45388), (b'U', 36308), (b'D', 33844),
+        (b'P', 31671), (b'M', 30129), (b'H', 30034), (b'G', 24705),
+        (b'B', 20720), (b'F', 18121), (b'Y', 17779), (b'W', 12899),
+        (b'K', 11016), (b'V', 10074), (b'X', 02902), (b'Z', 02722),
+        (b'J', 01965), (b'Q', 01962),
+    ].iter().cloned().collect();
+
+    let mut char_counts: HashMap<u8, usize> = HashMap::new();
+
+    let mut non_ascii = 0;
+
+    for c in s {
+        if c.is_ascii_alphanumeric() || c.is_ascii_whitespace() {
+            let key = c.to_ascii_uppercase();
+            match char_counts.get_mut(&key) {
+                Some(count) => *count += 1,
+                None => {
+                    char_counts.insert(key, 1);
+                    ()
+                },
+            }
+        } else {
+            non_ascii += 1;
+        }
+    }
+
+    let diff: i32 = expected
+        .iter()
+        .map(|(c, expectation)| {
+            let &actual = char_counts.get(c).unwrap_or(&0);
+            let rate: i32 = 100 * actual as i32 / s.len() as i32;
+            (rate - expectation).abs()
+        })
+        .sum();
+
+    diff as usize * 100 + non_ascii * 100
+}
+
+/// Finds the byte that, xored with every byte of `data`, gives the most
+/// English-looking result.
+pub fn single_byte_xor_key(data: &[u8]) -> u8 {
+    (0..=255)
+        .min_by_key(|&k| {
+            score_string(&fixed_xor(data, &vec![k; data.len()]))
+        })
+        .unwrap()
+}
+
+#[test]
+fn single_byte_xor_key_test() {
+    let plaintext = b"Cooking MC's like a pound of bacon";
+    let key = vec![0x58; plaintext.len()];
+    assert_eq!(0x58, single_byte_xor_key(&fixed_xor(plaintext, &key)));
+}
+
+/// A small splitmix64 generator for making up keys and test inputs
+/// without pulling in a dependency. Not suitable for anything that
+/// actually needs to be unpredictable.
+#[allow(dead_code)]
+pub struct Rng(u64);
+
+#[allow(dead_code)]
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn bytes(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next_u64() as u8).collect()
+    }
+}
+
+#[test]
+fn rng_test() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    assert_eq!(a.bytes(32), b.bytes(32));
+    assert_ne!(a.bytes(32), Rng::new(43).bytes(32));
+}